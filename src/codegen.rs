@@ -0,0 +1,137 @@
+use std::fmt;
+
+use crate::{Expr, Spanned};
+
+/// Raised when an [`Expr`] construct has no JavaScript representation.
+#[derive(Debug, Clone)]
+pub struct CodegenError {
+    pub message: String,
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Emits JavaScript source for an [`Expr`] tree.
+#[derive(Debug, Default)]
+pub struct Codegen;
+
+impl Codegen {
+    pub fn new() -> Self {
+        Codegen
+    }
+
+    pub fn emit_expr(&mut self, expr: &Expr) -> Result<String, CodegenError> {
+        match expr {
+            Expr::Num(n) => Ok(n.to_string()),
+            Expr::Bool(b) => Ok(b.to_string()),
+            Expr::Str(s) => Ok(format!("{s:?}")),
+            Expr::Reference(name) => Ok((*name).to_string()),
+
+            Expr::Neg(inner) => Ok(format!("(-{})", self.emit_expr(&inner.0)?)),
+            Expr::Add(lhs, rhs) => self.emit_binop("+", lhs, rhs),
+            Expr::Sub(lhs, rhs) => self.emit_binop("-", lhs, rhs),
+            Expr::Mul(lhs, rhs) => self.emit_binop("*", lhs, rhs),
+            Expr::Div(lhs, rhs) => self.emit_binop("/", lhs, rhs),
+            Expr::Eq(lhs, rhs) => self.emit_binop("===", lhs, rhs),
+            Expr::Lt(lhs, rhs) => self.emit_binop("<", lhs, rhs),
+            Expr::Gt(lhs, rhs) => self.emit_binop(">", lhs, rhs),
+
+            Expr::List(items) => {
+                let mut elems = Vec::with_capacity(items.len());
+                for (item, _) in items {
+                    elems.push(self.emit_expr(item)?);
+                }
+                Ok(format!("[{}]", elems.join(", ")))
+            }
+            Expr::If { cond, then, r#else } => Ok(format!(
+                "({} ? {} : {})",
+                self.emit_expr(&cond.0)?,
+                self.emit_expr(&then.0)?,
+                self.emit_expr(&r#else.0)?
+            )),
+
+            Expr::Binding { name, value } => {
+                Ok(format!("const {name} = {};", self.emit_expr(&value.0)?))
+            }
+            Expr::LetIn { bindings, body } => {
+                let mut stmts = String::new();
+                for (binding, _) in bindings {
+                    stmts.push_str(&self.emit_expr(binding)?);
+                    stmts.push(' ');
+                }
+                Ok(format!(
+                    "(() => {{ {stmts}return {}; }})()",
+                    self.emit_expr(&body.0)?
+                ))
+            }
+
+            Expr::Call(f, args) => {
+                let mut js = (*f).to_string();
+                for (arg, _) in args {
+                    js.push('(');
+                    js.push_str(&self.emit_expr(arg)?);
+                    js.push(')');
+                }
+                Ok(js)
+            }
+            Expr::Lambda { arg, body } => Ok(format!("({arg}) => {}", self.emit_expr(&body.0)?)),
+        }
+    }
+
+    fn emit_binop(
+        &mut self,
+        op: &str,
+        lhs: &Spanned<Expr>,
+        rhs: &Spanned<Expr>,
+    ) -> Result<String, CodegenError> {
+        Ok(format!(
+            "({} {op} {})",
+            self.emit_expr(&lhs.0)?,
+            self.emit_expr(&rhs.0)?
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chumsky::{input::Input, Parser};
+
+    use crate::{lexer, parser, Span};
+
+    fn emit_source(src: &str) -> String {
+        let tokens = lexer::lexer().parse(src).into_result().unwrap();
+        let eoi = Span::splat(src.len());
+        let ast = parser()
+            .parse(tokens.as_slice().map(eoi, |(t, s)| (t, s)))
+            .into_result()
+            .unwrap();
+        Codegen::new().emit_expr(&ast.0).unwrap()
+    }
+
+    #[test]
+    fn emits_if_as_ternary() {
+        assert_eq!(emit_source("if true then 1 else 2"), "(true ? 1 : 2)");
+    }
+
+    #[test]
+    fn emits_list_literal() {
+        assert_eq!(emit_source("[1 2 3]"), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn emits_lambda_as_arrow_function() {
+        assert_eq!(emit_source("x: x + 1"), "(x) => (x + 1)");
+    }
+
+    #[test]
+    fn emits_call_as_curried_invocation() {
+        assert_eq!(
+            emit_source("let add = a: b: a + b; in add 2 3"),
+            "(() => { const add = (a) => (b) => (a + b); return add(2)(3); })()"
+        );
+    }
+}
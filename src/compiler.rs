@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+
+use crate::Expr;
+
+/// A single instruction in the flat, stack-based IR that [`Compiler`] lowers
+/// an [`Expr`] tree into.
+#[derive(Debug, Clone)]
+pub enum Instr {
+    /// Push a numeric literal onto the operand stack.
+    NumPush(f64),
+    /// Push a boolean literal onto the operand stack.
+    BoolPush(bool),
+    /// Push a string literal onto the operand stack.
+    StrPush(String),
+    /// Look a name up in the current scope and push its value.
+    Get(String),
+
+    Neg,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Lt,
+    Gt,
+
+    /// Take the top `count` values off the stack and push a single
+    /// `Value::List` holding them in the order they were pushed.
+    ListMake(usize),
+    /// Pop a condition off the stack; if false, skip forward over `count`
+    /// more instructions (the `then` branch plus its trailing `Jump`).
+    JumpIfFalse(usize),
+    /// Unconditionally skip forward over `count` more instructions (used to
+    /// jump an executed `then` branch past the `else` branch).
+    Jump(usize),
+
+    /// Push a fresh lexical scope, used when entering a `let ... in`.
+    ScopePush,
+    /// Pop the innermost lexical scope, used when leaving a `let ... in`.
+    ScopePop,
+    /// Pop the top of the operand stack and bind it to `name` in the
+    /// current scope.
+    Bind(String),
+
+    /// Build a closure over the current scope, capturing `arg` and `body`.
+    FuncMake(String, Vec<Instr>),
+    /// Pop a function and an argument off the operand stack, apply the
+    /// function, and push the result.
+    FuncApply,
+}
+
+/// Lowers an [`Expr`] tree into a flat [`Instr`] vector, post-order: operands
+/// are compiled before the operator that consumes them.
+#[derive(Debug, Default)]
+pub struct Compiler;
+
+impl Compiler {
+    pub fn new() -> Self {
+        Compiler
+    }
+
+    pub fn compile_expr(&mut self, expr: &Expr) -> Vec<Instr> {
+        match expr {
+            Expr::Num(n) => vec![Instr::NumPush(*n)],
+            Expr::Bool(b) => vec![Instr::BoolPush(*b)],
+            Expr::Str(s) => vec![Instr::StrPush(s.clone())],
+            Expr::Reference(name) => vec![Instr::Get((*name).to_string())],
+
+            Expr::Neg(inner) => {
+                let mut instrs = self.compile_expr(&inner.0);
+                instrs.push(Instr::Neg);
+                instrs
+            }
+            Expr::Add(lhs, rhs) => self.compile_binop(lhs, rhs, Instr::Add),
+            Expr::Sub(lhs, rhs) => self.compile_binop(lhs, rhs, Instr::Sub),
+            Expr::Mul(lhs, rhs) => self.compile_binop(lhs, rhs, Instr::Mul),
+            Expr::Div(lhs, rhs) => self.compile_binop(lhs, rhs, Instr::Div),
+            Expr::Eq(lhs, rhs) => self.compile_binop(lhs, rhs, Instr::Eq),
+            Expr::Lt(lhs, rhs) => self.compile_binop(lhs, rhs, Instr::Lt),
+            Expr::Gt(lhs, rhs) => self.compile_binop(lhs, rhs, Instr::Gt),
+
+            Expr::List(items) => {
+                let mut instrs = Vec::new();
+                for (item, _) in items {
+                    instrs.extend(self.compile_expr(item));
+                }
+                instrs.push(Instr::ListMake(items.len()));
+                instrs
+            }
+            Expr::If { cond, then, r#else } => {
+                let mut instrs = self.compile_expr(&cond.0);
+                let then_instrs = self.compile_expr(&then.0);
+                let else_instrs = self.compile_expr(&r#else.0);
+                instrs.push(Instr::JumpIfFalse(then_instrs.len() + 1));
+                instrs.extend(then_instrs);
+                instrs.push(Instr::Jump(else_instrs.len()));
+                instrs.extend(else_instrs);
+                instrs
+            }
+
+            Expr::Binding { name, value } => {
+                let mut instrs = self.compile_expr(&value.0);
+                instrs.push(Instr::Bind(name.to_string()));
+                instrs
+            }
+            Expr::LetIn { bindings, body } => {
+                let mut instrs = vec![Instr::ScopePush];
+                for (binding, _) in bindings {
+                    instrs.extend(self.compile_expr(binding));
+                }
+                instrs.extend(self.compile_expr(&body.0));
+                instrs.push(Instr::ScopePop);
+                instrs
+            }
+
+            Expr::Call(f, args) => {
+                let mut instrs = vec![Instr::Get((*f).to_string())];
+                for (arg, _) in args {
+                    instrs.extend(self.compile_expr(arg));
+                    instrs.push(Instr::FuncApply);
+                }
+                instrs
+            }
+            Expr::Lambda { arg, body } => {
+                let body_instrs = self.compile_expr(&body.0);
+                vec![Instr::FuncMake(arg.to_string(), body_instrs)]
+            }
+        }
+    }
+
+    fn compile_binop(&mut self, lhs: &crate::Spanned<Expr>, rhs: &crate::Spanned<Expr>, op: Instr) -> Vec<Instr> {
+        let mut instrs = self.compile_expr(&lhs.0);
+        instrs.extend(self.compile_expr(&rhs.0));
+        instrs.push(op);
+        instrs
+    }
+}
+
+/// A runtime value produced by the [`Vm`].
+#[derive(Debug, Clone)]
+pub enum Value {
+    Num(f64),
+    Bool(bool),
+    // Constructed by `StrPush`/`ListMake` and printed via `{:?}`, but no
+    // instruction yet reads their contents back out.
+    #[allow(dead_code)]
+    Str(String),
+    #[allow(dead_code)]
+    List(Vec<Value>),
+    Func {
+        arg: String,
+        body: Vec<Instr>,
+        env: Vec<HashMap<String, Value>>,
+    },
+}
+
+impl Value {
+    fn as_num(&self) -> f64 {
+        match self {
+            Value::Num(n) => *n,
+            other => panic!("expected a number, found {other:?}"),
+        }
+    }
+
+    fn as_bool(&self) -> bool {
+        match self {
+            Value::Bool(b) => *b,
+            other => panic!("expected a bool, found {other:?}"),
+        }
+    }
+}
+
+/// A stack-based VM that executes the [`Instr`] IR produced by [`Compiler`].
+///
+/// Execution state is an operand stack plus a stack of lexical scopes
+/// (`HashMap<String, Value>`), innermost scope last.
+pub struct Vm {
+    stack: Vec<Value>,
+    scopes: Vec<HashMap<String, Value>>,
+}
+
+impl Default for Vm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vm {
+    pub fn new() -> Self {
+        Vm {
+            stack: Vec::new(),
+            scopes: vec![HashMap::new()],
+        }
+    }
+
+    pub fn run(&mut self, instrs: &[Instr]) -> Value {
+        let mut pc = 0;
+        while pc < instrs.len() {
+            pc = match self.exec(&instrs[pc]) {
+                Some(jump) => (pc as isize + jump) as usize,
+                None => pc + 1,
+            };
+        }
+        self.stack.pop().expect("VM halted with an empty stack")
+    }
+
+    /// Bind `name` to `value` in the innermost scope. Lets callers that
+    /// drive the VM one expression at a time (the REPL) persist bindings
+    /// across runs without compiling a `Bind` instruction for each one.
+    pub fn bind(&mut self, name: &str, value: Value) {
+        self.scopes
+            .last_mut()
+            .expect("scope stack is never empty")
+            .insert(name.to_string(), value);
+    }
+
+    /// Execute one instruction. Returns `Some(delta)` when it changed control
+    /// flow (the caller should move the program counter by `delta` instead
+    /// of advancing by one), `None` otherwise.
+    fn exec(&mut self, instr: &Instr) -> Option<isize> {
+        match instr {
+            Instr::NumPush(n) => self.stack.push(Value::Num(*n)),
+            Instr::BoolPush(b) => self.stack.push(Value::Bool(*b)),
+            Instr::StrPush(s) => self.stack.push(Value::Str(s.clone())),
+            Instr::Get(name) => {
+                let value = self
+                    .scopes
+                    .iter()
+                    .rev()
+                    .find_map(|scope| scope.get(name))
+                    .unwrap_or_else(|| panic!("unbound name `{name}`"))
+                    .clone();
+                self.stack.push(value);
+            }
+
+            Instr::Neg => {
+                let v = self.stack.pop().unwrap().as_num();
+                self.stack.push(Value::Num(-v));
+            }
+            Instr::Add => self.numeric_binop(|a, b| Value::Num(a + b)),
+            Instr::Sub => self.numeric_binop(|a, b| Value::Num(a - b)),
+            Instr::Mul => self.numeric_binop(|a, b| Value::Num(a * b)),
+            Instr::Div => self.numeric_binop(|a, b| Value::Num(a / b)),
+            Instr::Eq => self.numeric_binop(|a, b| Value::Bool(a == b)),
+            Instr::Lt => self.numeric_binop(|a, b| Value::Bool(a < b)),
+            Instr::Gt => self.numeric_binop(|a, b| Value::Bool(a > b)),
+
+            Instr::ListMake(count) => {
+                let items = self.stack.split_off(self.stack.len() - count);
+                self.stack.push(Value::List(items));
+            }
+            Instr::JumpIfFalse(count) => {
+                let cond = self.stack.pop().unwrap().as_bool();
+                if !cond {
+                    return Some(1 + *count as isize);
+                }
+            }
+            Instr::Jump(count) => return Some(1 + *count as isize),
+
+            Instr::ScopePush => self.scopes.push(HashMap::new()),
+            Instr::ScopePop => {
+                self.stack_pop_scope();
+            }
+            Instr::Bind(name) => {
+                let value = self.stack.pop().unwrap();
+                self.scopes
+                    .last_mut()
+                    .expect("scope stack is never empty")
+                    .insert(name.clone(), value);
+            }
+
+            Instr::FuncMake(arg, body) => self.stack.push(Value::Func {
+                arg: arg.clone(),
+                body: body.clone(),
+                env: self.scopes.clone(),
+            }),
+            Instr::FuncApply => {
+                let arg_value = self.stack.pop().unwrap();
+                let func = self.stack.pop().unwrap();
+                match func {
+                    Value::Func { arg, body, env } => {
+                        let mut call_scopes = env;
+                        call_scopes.push(HashMap::from([(arg, arg_value)]));
+                        let mut callee = Vm {
+                            stack: Vec::new(),
+                            scopes: call_scopes,
+                        };
+                        self.stack.push(callee.run(&body));
+                    }
+                    other => panic!("attempted to call {other:?} as a function"),
+                }
+            }
+        }
+        None
+    }
+
+    fn numeric_binop(&mut self, f: impl FnOnce(f64, f64) -> Value) {
+        let rhs = self.stack.pop().unwrap().as_num();
+        let lhs = self.stack.pop().unwrap().as_num();
+        self.stack.push(f(lhs, rhs));
+    }
+
+    fn stack_pop_scope(&mut self) {
+        // `LetIn`'s body result sits on the operand stack, which is separate
+        // from the scope stack, so popping the scope here is safe even
+        // though it happens after the body has already run.
+        self.scopes.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chumsky::{input::Input, Parser};
+
+    use crate::{lexer, parser, Span};
+
+    fn eval_source(src: &str) -> Value {
+        let tokens = lexer::lexer().parse(src).into_result().unwrap();
+        let eoi = Span::splat(src.len());
+        let ast = parser()
+            .parse(tokens.as_slice().map(eoi, |(t, s)| (t, s)))
+            .into_result()
+            .unwrap();
+        let instrs = Compiler::new().compile_expr(&ast.0);
+        Vm::new().run(&instrs)
+    }
+
+    #[test]
+    fn calls_a_lambda() {
+        let value = eval_source("let double = x: x * 2; in double 5");
+        assert!(matches!(value, Value::Num(n) if n == 10.0));
+    }
+
+    #[test]
+    fn curries_a_multi_arg_call() {
+        let value = eval_source("let add = a: b: a + b; in add 2 3");
+        assert!(matches!(value, Value::Num(n) if n == 5.0));
+    }
+}
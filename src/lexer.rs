@@ -0,0 +1,169 @@
+use std::fmt;
+
+use chumsky::{error::Rich, prelude::*};
+
+use crate::Span;
+
+/// Reserved words, kept distinct from [`Token::Ident`] so the parser can
+/// match on them directly instead of re-checking string contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keyword {
+    Let,
+    In,
+    If,
+    Then,
+    Else,
+    True,
+    False,
+}
+
+/// A lexical token. `Ident`, `Num`, and `Str` keep a `&str` slice of the
+/// source rather than an owned/parsed value so the lexer stays a pure
+/// tokenizer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Token<'a> {
+    Ident(&'a str),
+    Num(&'a str),
+    Str(&'a str),
+    Keyword(Keyword),
+
+    Eq,
+    EqEq,
+    Lt,
+    Gt,
+    Semi,
+    Colon,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "{s}"),
+            Token::Num(s) => write!(f, "{s}"),
+            Token::Str(s) => write!(f, "\"{s}\""),
+            Token::Keyword(Keyword::Let) => write!(f, "let"),
+            Token::Keyword(Keyword::In) => write!(f, "in"),
+            Token::Keyword(Keyword::If) => write!(f, "if"),
+            Token::Keyword(Keyword::Then) => write!(f, "then"),
+            Token::Keyword(Keyword::Else) => write!(f, "else"),
+            Token::Keyword(Keyword::True) => write!(f, "true"),
+            Token::Keyword(Keyword::False) => write!(f, "false"),
+            Token::Eq => write!(f, "="),
+            Token::EqEq => write!(f, "=="),
+            Token::Lt => write!(f, "<"),
+            Token::Gt => write!(f, ">"),
+            Token::Semi => write!(f, ";"),
+            Token::Colon => write!(f, ":"),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+            Token::LBracket => write!(f, "["),
+            Token::RBracket => write!(f, "]"),
+            Token::Plus => write!(f, "+"),
+            Token::Minus => write!(f, "-"),
+            Token::Star => write!(f, "*"),
+            Token::Slash => write!(f, "/"),
+        }
+    }
+}
+
+/// Tokenizes a source string into a spanned token stream, separating
+/// lexical concerns (keywords, punctuation) from the grammar in [`crate::parser`].
+pub fn lexer<'a>() -> impl Parser<'a, &'a str, Vec<(Token<'a>, Span)>, extra::Err<Rich<'a, char>>> {
+    let num = text::int(10).map(Token::Num);
+
+    let ident = text::ident().map(|s: &str| match s {
+        "let" => Token::Keyword(Keyword::Let),
+        "in" => Token::Keyword(Keyword::In),
+        "if" => Token::Keyword(Keyword::If),
+        "then" => Token::Keyword(Keyword::Then),
+        "else" => Token::Keyword(Keyword::Else),
+        "true" => Token::Keyword(Keyword::True),
+        "false" => Token::Keyword(Keyword::False),
+        _ => Token::Ident(s),
+    });
+
+    let string = just('"')
+        .ignore_then(any().filter(|c: &char| *c != '"').repeated().to_slice())
+        .then_ignore(just('"'))
+        .map(Token::Str);
+
+    let ctrl = choice((
+        just("==").to(Token::EqEq),
+        just('=').to(Token::Eq),
+        just(';').to(Token::Semi),
+        just(':').to(Token::Colon),
+        just('(').to(Token::LParen),
+        just(')').to(Token::RParen),
+        just('[').to(Token::LBracket),
+        just(']').to(Token::RBracket),
+        just('<').to(Token::Lt),
+        just('>').to(Token::Gt),
+        just('+').to(Token::Plus),
+        just('-').to(Token::Minus),
+        just('*').to(Token::Star),
+        just('/').to(Token::Slash),
+    ));
+
+    let token = num.or(ident).or(string).or(ctrl);
+
+    token
+        .map_with(|tok, extra| (tok, extra.span()))
+        .padded()
+        .repeated()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens_of(src: &str) -> Vec<Token<'_>> {
+        lexer()
+            .parse(src)
+            .into_result()
+            .unwrap()
+            .into_iter()
+            .map(|(tok, _)| tok)
+            .collect()
+    }
+
+    #[test]
+    fn keyword_does_not_swallow_longer_identifier() {
+        assert_eq!(tokens_of("let"), vec![Token::Keyword(Keyword::Let)]);
+        assert_eq!(tokens_of("letx"), vec![Token::Ident("letx")]);
+    }
+
+    #[test]
+    fn lexes_string_literal_boundaries() {
+        assert_eq!(tokens_of("\"hi\""), vec![Token::Str("hi")]);
+    }
+
+    #[test]
+    fn lexes_eq_eq_before_eq() {
+        assert_eq!(tokens_of("=="), vec![Token::EqEq]);
+        assert_eq!(tokens_of("="), vec![Token::Eq]);
+    }
+
+    #[test]
+    fn lexes_a_full_expression() {
+        assert_eq!(
+            tokens_of("x = 1 + 2;"),
+            vec![
+                Token::Ident("x"),
+                Token::Eq,
+                Token::Num("1"),
+                Token::Plus,
+                Token::Num("2"),
+                Token::Semi,
+            ]
+        );
+    }
+}
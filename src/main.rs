@@ -1,148 +1,376 @@
 use ariadne::{sources, Color, Label, Report, ReportKind};
-use chumsky::{error::Rich, prelude::*};
+use chumsky::{
+    error::Rich,
+    input::{Input, ValueInput},
+    prelude::*,
+};
+
+use lexer::{Keyword, Token};
+
+mod codegen;
+mod compiler;
+mod lexer;
+mod repl;
+mod typing;
+
+/// A source span, as produced by chumsky's `map_with`.
+pub type Span = SimpleSpan;
+/// A node paired with the span of source text it was parsed from.
+pub type Spanned<T> = (T, Span);
 
 #[derive(Debug)]
 pub enum Expr<'a> {
     Num(f64),
+    Bool(bool),
+    Str(String),
     Reference(&'a str),
 
-    Neg(Box<Expr<'a>>),
-    Add(Box<Expr<'a>>, Box<Expr<'a>>),
-    Sub(Box<Expr<'a>>, Box<Expr<'a>>),
-    Mul(Box<Expr<'a>>, Box<Expr<'a>>),
-    Div(Box<Expr<'a>>, Box<Expr<'a>>),
+    Neg(Box<Spanned<Expr<'a>>>),
+    Add(Box<Spanned<Expr<'a>>>, Box<Spanned<Expr<'a>>>),
+    Sub(Box<Spanned<Expr<'a>>>, Box<Spanned<Expr<'a>>>),
+    Mul(Box<Spanned<Expr<'a>>>, Box<Spanned<Expr<'a>>>),
+    Div(Box<Spanned<Expr<'a>>>, Box<Spanned<Expr<'a>>>),
+
+    Eq(Box<Spanned<Expr<'a>>>, Box<Spanned<Expr<'a>>>),
+    Lt(Box<Spanned<Expr<'a>>>, Box<Spanned<Expr<'a>>>),
+    Gt(Box<Spanned<Expr<'a>>>, Box<Spanned<Expr<'a>>>),
+
+    List(Vec<Spanned<Expr<'a>>>),
+    If {
+        cond: Box<Spanned<Expr<'a>>>,
+        then: Box<Spanned<Expr<'a>>>,
+        r#else: Box<Spanned<Expr<'a>>>,
+    },
 
     Binding {
         name: &'a str,
-        value: Box<Expr<'a>>,
+        value: Box<Spanned<Expr<'a>>>,
     },
     LetIn {
-        bindings: Vec<Expr<'a>>,
-        body: Box<Expr<'a>>,
+        bindings: Vec<Spanned<Expr<'a>>>,
+        body: Box<Spanned<Expr<'a>>>,
     },
 
-    Call(&'a str, Vec<Expr<'a>>),
+    Call(&'a str, Vec<Spanned<Expr<'a>>>),
     Lambda {
         arg: &'a str,
-        body: Box<Expr<'a>>,
+        body: Box<Spanned<Expr<'a>>>,
     },
 }
 
-pub fn parser<'a>() -> impl Parser<'a, &'a str, Expr<'a>, extra::Err<Rich<'a, char>>> {
-    let ident = text::ident().padded();
+/// Join two spans into the range that covers both.
+fn join(a: Span, b: Span) -> Span {
+    Span::new(a.start(), b.end())
+}
+
+pub fn parser<'tokens, 'src: 'tokens, I>() -> impl Parser<
+    'tokens,
+    I,
+    Spanned<Expr<'src>>,
+    extra::Err<Rich<'tokens, Token<'src>, Span>>,
+> + Clone
+where
+    I: ValueInput<'tokens, Token = Token<'src>, Span = Span>,
+{
+    let ident = select! { Token::Ident(s) => s };
 
     let expr = recursive(|expr| {
-        let int = text::int(10)
+        let int = select! { Token::Num(s) => s }
             .map(|s: &str| Expr::Num(s.parse().unwrap()))
-            .padded();
+            .map_with(|e, extra| (e, extra.span()));
+
+        let boolean = just(Token::Keyword(Keyword::True))
+            .map(|_| Expr::Bool(true))
+            .or(just(Token::Keyword(Keyword::False)).map(|_| Expr::Bool(false)))
+            .map_with(|e, extra| (e, extra.span()));
+
+        let string = select! { Token::Str(s) => s }
+            .map(|s: &str| Expr::Str(s.to_string()))
+            .map_with(|e, extra| (e, extra.span()));
+
+        let list = expr
+            .clone()
+            .repeated()
+            .collect::<Vec<_>>()
+            .delimited_by(just(Token::LBracket), just(Token::RBracket))
+            .map_with(|items, extra| (Expr::List(items), extra.span()));
+
+        let if_then_else = just(Token::Keyword(Keyword::If))
+            .ignore_then(expr.clone())
+            .then_ignore(just(Token::Keyword(Keyword::Then)))
+            .then(expr.clone())
+            .then_ignore(just(Token::Keyword(Keyword::Else)))
+            .then(expr.clone())
+            .map_with(|((cond, then), r#else), extra| {
+                (
+                    Expr::If {
+                        cond: Box::new(cond),
+                        then: Box::new(then),
+                        r#else: Box::new(r#else),
+                    },
+                    extra.span(),
+                )
+            });
 
         let call = ident
-            .then(
-                expr.clone()
-                    .padded()
-                    .repeated()
-                    .at_least(1)
-                    .collect::<Vec<_>>(),
-            )
-            .map(|(f, args)| Expr::Call(f, args));
+            .then(expr.clone().repeated().at_least(1).collect::<Vec<_>>())
+            .map_with(|(f, args), extra| (Expr::Call(f, args), extra.span()));
 
         let atom = int
-            .or(expr.delimited_by(just('('), just(')')))
+            .or(boolean)
+            .or(string)
+            .or(list)
+            .or(if_then_else)
+            .or(expr
+                .clone()
+                .delimited_by(just(Token::LParen), just(Token::RParen)))
             .or(call)
-            .or(ident.map(Expr::Reference));
-
-        let op = |c| just(c).padded();
+            .or(ident.map_with(|name, extra| (Expr::Reference(name), extra.span())));
 
-        let unary = op('-')
+        let unary = just(Token::Minus)
+            .map_with(|_, extra| extra.span())
             .repeated()
-            .foldr(atom, |_op, rhs| Expr::Neg(Box::new(rhs)));
+            .foldr(atom, |op_span, rhs| {
+                let span = join(op_span, rhs.1);
+                (Expr::Neg(Box::new(rhs)), span)
+            });
 
         let product = unary.clone().foldl(
             choice((
-                op('*').to(Expr::Mul as fn(_, _) -> _),
-                op('/').to(Expr::Div as fn(_, _) -> _),
+                just(Token::Star).to(Expr::Mul as fn(_, _) -> _),
+                just(Token::Slash).to(Expr::Div as fn(_, _) -> _),
             ))
             .then(unary)
             .repeated(),
-            |lhs, (op, rhs)| op(Box::new(lhs), Box::new(rhs)),
+            |lhs, (op, rhs)| {
+                let span = join(lhs.1, rhs.1);
+                (op(Box::new(lhs), Box::new(rhs)), span)
+            },
         );
 
         let sum = product.clone().foldl(
             choice((
-                op('+').to(Expr::Add as fn(_, _) -> _),
-                op('-').to(Expr::Sub as fn(_, _) -> _),
+                just(Token::Plus).to(Expr::Add as fn(_, _) -> _),
+                just(Token::Minus).to(Expr::Sub as fn(_, _) -> _),
             ))
             .then(product)
             .repeated(),
-            |lhs, (op, rhs)| op(Box::new(lhs), Box::new(rhs)),
+            |lhs, (op, rhs)| {
+                let span = join(lhs.1, rhs.1);
+                (op(Box::new(lhs), Box::new(rhs)), span)
+            },
         );
 
-        sum
+        sum.clone().foldl(
+            choice((
+                just(Token::EqEq).to(Expr::Eq as fn(_, _) -> _),
+                just(Token::Lt).to(Expr::Lt as fn(_, _) -> _),
+                just(Token::Gt).to(Expr::Gt as fn(_, _) -> _),
+            ))
+            .then(sum)
+            .repeated(),
+            |lhs, (op, rhs)| {
+                let span = join(lhs.1, rhs.1);
+                (op(Box::new(lhs), Box::new(rhs)), span)
+            },
+        )
     });
 
-    let decl = recursive(|decl| {
+    recursive(|decl| {
         let binding = ident
-            .then_ignore(just('='))
+            .then_ignore(just(Token::Eq))
             .then(decl.clone())
-            .then_ignore(just(';'))
-            .padded()
-            .map(|(ident, expr)| Expr::Binding {
-                name: ident,
-                value: Box::new(expr),
+            .then_ignore(just(Token::Semi))
+            .map_with(|(ident, expr), extra| {
+                (
+                    Expr::Binding {
+                        name: ident,
+                        value: Box::new(expr),
+                    },
+                    extra.span(),
+                )
             })
             .labelled("binding");
 
-        let let_in = text::keyword("let")
+        let let_in = just(Token::Keyword(Keyword::Let))
             .ignore_then(binding.repeated().collect())
-            .then_ignore(text::keyword("in"))
+            .then_ignore(just(Token::Keyword(Keyword::In)))
             .then(decl.clone())
-            .map(|(bindings, body)| Expr::LetIn {
-                bindings,
-                body: Box::new(body),
+            .map_with(|(bindings, body), extra| {
+                (
+                    Expr::LetIn {
+                        bindings,
+                        body: Box::new(body),
+                    },
+                    extra.span(),
+                )
             })
             .labelled("let-in");
 
         let func = ident
-            .then_ignore(just(':'))
+            .then_ignore(just(Token::Colon))
             .then(decl.clone())
-            .map(|(ident, expr)| Expr::Lambda {
-                arg: ident,
-                body: Box::new(expr),
+            .map_with(|(ident, expr), extra| {
+                (
+                    Expr::Lambda {
+                        arg: ident,
+                        body: Box::new(expr),
+                    },
+                    extra.span(),
+                )
             });
 
-        let_in.or(func).or(expr).padded()
-    });
+        let_in.or(func).or(expr)
+    })
+}
 
-    decl
+/// Render a batch of `Rich` errors (already mapped to string tokens) through
+/// the shared ariadne reporting style.
+fn report_errors(file_name: &str, src: &str, errs: Vec<Rich<String>>) {
+    errs.into_iter().for_each(|e| {
+        Report::build(ReportKind::Error, file_name.to_string(), e.span().start)
+            .with_message(e.to_string())
+            .with_label(
+                Label::new((file_name.to_string(), e.span().into_range()))
+                    .with_message(e.reason().to_string())
+                    .with_color(Color::Red),
+            )
+            .with_labels(e.contexts().map(|(label, span)| {
+                Label::new((file_name.to_string(), span.into_range()))
+                    .with_message(format!("while parsing this {}", label))
+                    .with_color(Color::Yellow)
+            }))
+            .finish()
+            .print(sources([(file_name.to_string(), src.to_string())]))
+            .unwrap()
+    });
 }
 
 fn main() {
-    let file_name = std::env::args().nth(1).unwrap();
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() < 2 || args.iter().any(|a| a == "--repl") {
+        repl::run();
+        return;
+    }
+
+    let file_name = args
+        .get(1)
+        .filter(|a| !a.starts_with("--"))
+        .expect("usage: nix-compiler <file> [--emit js] | --repl")
+        .clone();
+    let emit_js = args.windows(2).any(|w| w[0] == "--emit" && w[1] == "js");
+
     let src = std::fs::read_to_string(&file_name).unwrap();
 
-    let (ast, errs) = parser().parse(&src).into_output_errors();
-    if !errs.is_empty() {
-        errs.into_iter()
+    let (tokens, lex_errs) = lexer::lexer().parse(&src).into_output_errors();
+    if !lex_errs.is_empty() {
+        let errs = lex_errs
+            .into_iter()
             .map(|e| e.map_token(|c| c.to_string()))
-            .for_each(|e| {
-                Report::build(ReportKind::Error, file_name.clone(), e.span().start)
-                    .with_message(e.to_string())
-                    .with_label(
-                        Label::new((file_name.clone(), e.span().into_range()))
-                            .with_message(e.reason().to_string())
-                            .with_color(Color::Red),
-                    )
-                    .with_labels(e.contexts().map(|(label, span)| {
-                        Label::new((file_name.clone(), span.into_range()))
-                            .with_message(format!("while parsing this {}", label))
-                            .with_color(Color::Yellow)
-                    }))
-                    .finish()
-                    .print(sources([(file_name.clone(), src.clone())]))
-                    .unwrap()
-            });
+            .collect();
+        report_errors(&file_name, &src, errs);
+        std::process::exit(1);
+    }
+    let tokens = tokens.expect("lexing succeeded but produced no tokens");
+
+    let eoi = Span::splat(src.len());
+    let (ast, errs) = parser()
+        .parse(tokens.as_slice().map(eoi, |(t, s)| (t, s)))
+        .into_output_errors();
+    if !errs.is_empty() {
+        let errs = errs
+            .into_iter()
+            .map(|e| e.map_token(|t| t.to_string()))
+            .collect();
+        report_errors(&file_name, &src, errs);
         std::process::exit(1);
     }
 
     println!("{:#?}", ast);
+
+    let ast = ast.expect("parse succeeded but produced no AST");
+
+    match typing::Infer::new().infer_expr(&ast) {
+        Ok(ty) => println!("type: {ty}"),
+        Err(e) => {
+            Report::build(ReportKind::Error, file_name.clone(), e.span.start)
+                .with_message("type error")
+                .with_label(
+                    Label::new((file_name.clone(), e.span.into_range()))
+                        .with_message(e.message)
+                        .with_color(Color::Red),
+                )
+                .finish()
+                .print(sources([(file_name.clone(), src.clone())]))
+                .unwrap();
+            std::process::exit(1);
+        }
+    }
+
+    if emit_js {
+        let js = codegen::Codegen::new()
+            .emit_expr(&ast.0)
+            .expect("expression has no JavaScript representation");
+        let out_path = std::path::Path::new(&file_name).with_extension("js");
+        std::fs::write(&out_path, js).unwrap();
+        println!("wrote {}", out_path.display());
+        return;
+    }
+
+    let instrs = compiler::Compiler::new().compile_expr(&ast.0);
+    let value = compiler::Vm::new().run(&instrs);
+    println!("{:?}", value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compiler::{Compiler, Value, Vm};
+
+    fn eval_source(src: &str) -> Value {
+        let tokens = lexer::lexer().parse(src).into_result().unwrap();
+        let eoi = Span::splat(src.len());
+        let ast = parser()
+            .parse(tokens.as_slice().map(eoi, |(t, s)| (t, s)))
+            .into_result()
+            .unwrap();
+        let instrs = Compiler::new().compile_expr(&ast.0);
+        Vm::new().run(&instrs)
+    }
+
+    #[test]
+    fn evaluates_bool_literals() {
+        assert!(matches!(eval_source("true"), Value::Bool(true)));
+        assert!(matches!(eval_source("false"), Value::Bool(false)));
+    }
+
+    #[test]
+    fn evaluates_string_literals() {
+        assert!(matches!(eval_source("\"hi\""), Value::Str(s) if s == "hi"));
+    }
+
+    #[test]
+    fn evaluates_list_literals() {
+        let value = eval_source("[1 2 3]");
+        let Value::List(items) = value else {
+            panic!("expected a list, got {value:?}");
+        };
+        assert!(matches!(items[0], Value::Num(n) if n == 1.0));
+        assert!(matches!(items[2], Value::Num(n) if n == 3.0));
+    }
+
+    #[test]
+    fn evaluates_if_then_else() {
+        assert!(matches!(eval_source("if 1 < 2 then 10 else 20"), Value::Num(n) if n == 10.0));
+        assert!(matches!(eval_source("if 2 < 1 then 10 else 20"), Value::Num(n) if n == 20.0));
+    }
+
+    #[test]
+    fn evaluates_comparisons() {
+        assert!(matches!(eval_source("1 == 1"), Value::Bool(true)));
+        assert!(matches!(eval_source("1 == 2"), Value::Bool(false)));
+        assert!(matches!(eval_source("1 < 2"), Value::Bool(true)));
+        assert!(matches!(eval_source("2 > 1"), Value::Bool(true)));
+    }
 }
@@ -0,0 +1,101 @@
+use ariadne::{sources, Color, Label, Report, ReportKind};
+use chumsky::{error::Rich, input::Input, Parser};
+
+use crate::compiler::{Compiler, Vm};
+use crate::{lexer, parser, typing, Expr, Span};
+
+const PROMPT_NAME: &str = "<repl>";
+
+/// Drop into an interactive readline loop: parse each entered line and
+/// either dump its AST or evaluate it. Each line stands alone: the parser
+/// accepts no top-level `name = value;` binding, and every line is
+/// type-checked against an empty environment, so no name defined on one
+/// line can be referenced on a later one.
+pub fn run() {
+    let mut rl = rustyline::DefaultEditor::new().expect("failed to start line editor");
+    let mut compiler = Compiler::new();
+    let mut vm = Vm::new();
+    let mut show_ast = false;
+
+    println!("nix-compiler REPL -- :ast toggles AST dump, :quit exits");
+
+    while let Ok(line) = rl.readline("nix> ") {
+        let _ = rl.add_history_entry(line.as_str());
+
+        let line = line.trim();
+        match line {
+            "" => continue,
+            ":quit" | ":q" => break,
+            ":ast" => {
+                show_ast = !show_ast;
+                println!("ast dump: {}", if show_ast { "on" } else { "off" });
+                continue;
+            }
+            _ => {}
+        }
+
+        let (tokens, lex_errs) = lexer::lexer().parse(line).into_output_errors();
+        if !lex_errs.is_empty() {
+            report_errors(line, lex_errs.into_iter().map(|e| e.map_token(|c| c.to_string())).collect());
+            continue;
+        }
+        let tokens = tokens.expect("lexing succeeded but produced no tokens");
+
+        let eoi = Span::splat(line.len());
+        let (ast, errs) = parser()
+            .parse(tokens.as_slice().map(eoi, |(t, s)| (t, s)))
+            .into_output_errors();
+        if !errs.is_empty() {
+            report_errors(line, errs.into_iter().map(|e| e.map_token(|t| t.to_string())).collect());
+            continue;
+        }
+        let Some(ast) = ast else { continue };
+
+        if show_ast {
+            println!("{:#?}", ast.0);
+            continue;
+        }
+
+        if let Err(e) = typing::Infer::new().infer_expr(&ast) {
+            Report::build(ReportKind::Error, PROMPT_NAME, e.span.start)
+                .with_message("type error")
+                .with_label(
+                    Label::new((PROMPT_NAME, e.span.into_range()))
+                        .with_message(e.message)
+                        .with_color(Color::Red),
+                )
+                .finish()
+                .print(sources([(PROMPT_NAME, line.to_string())]))
+                .unwrap();
+            continue;
+        }
+
+        match &ast.0 {
+            Expr::Binding { name, value } => {
+                let instrs = compiler.compile_expr(&value.0);
+                let value = vm.run(&instrs);
+                vm.bind(name, value.clone());
+                println!("{name} = {value:?}");
+            }
+            expr => {
+                let instrs = compiler.compile_expr(expr);
+                println!("{:?}", vm.run(&instrs));
+            }
+        }
+    }
+}
+
+fn report_errors(src: &str, errs: Vec<Rich<String>>) {
+    errs.into_iter().for_each(|e| {
+        Report::build(ReportKind::Error, PROMPT_NAME, e.span().start)
+            .with_message(e.to_string())
+            .with_label(
+                Label::new((PROMPT_NAME, e.span().into_range()))
+                    .with_message(e.reason().to_string())
+                    .with_color(Color::Red),
+            )
+            .finish()
+            .print(sources([(PROMPT_NAME, src.to_string())]))
+            .unwrap()
+    });
+}
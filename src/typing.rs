@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{Expr, Span, Spanned};
+
+/// A Hindley-Milner type: either a not-yet-resolved type variable, one of
+/// the ground types (number, bool, string), a list, or a function arrow.
+// The `T` prefix mirrors the type-theory notation (TVar, TArrow, ...) rather
+// than naming a type after its own name.
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    TVar(usize),
+    TNum,
+    TBool,
+    TStr,
+    TList(Box<Type>),
+    TArrow(Box<Type>, Box<Type>),
+}
+
+/// A `let`-bound name's type, universally quantified over `vars`.
+#[derive(Debug, Clone)]
+struct Scheme {
+    vars: Vec<usize>,
+    ty: Type,
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+type Env = HashMap<String, Scheme>;
+
+/// Algorithm W over [`Expr`]: infers a [`Type`] for an expression, reporting
+/// a [`TypeError`] on the first unification failure.
+#[derive(Default)]
+pub struct Infer {
+    subst: HashMap<usize, Type>,
+    counter: usize,
+}
+
+impl Infer {
+    pub fn new() -> Self {
+        Infer {
+            subst: HashMap::new(),
+            counter: 0,
+        }
+    }
+
+    /// Infer the type of a top-level expression against an empty environment.
+    pub fn infer_expr(&mut self, expr: &Spanned<Expr>) -> Result<Type, TypeError> {
+        let env = Env::new();
+        let ty = self.infer(&env, expr)?;
+        Ok(self.resolve(&ty))
+    }
+
+    fn fresh(&mut self) -> Type {
+        let id = self.counter;
+        self.counter += 1;
+        Type::TVar(id)
+    }
+
+    fn infer(&mut self, env: &Env, expr: &Spanned<Expr>) -> Result<Type, TypeError> {
+        let span = expr.1;
+        match &expr.0 {
+            Expr::Num(_) => Ok(Type::TNum),
+            Expr::Bool(_) => Ok(Type::TBool),
+            Expr::Str(_) => Ok(Type::TStr),
+
+            Expr::Reference(name) => {
+                let scheme = env
+                    .get(*name)
+                    .ok_or_else(|| TypeError {
+                        message: format!("unbound name `{name}`"),
+                        span,
+                    })?
+                    .clone();
+                Ok(self.instantiate(&scheme))
+            }
+
+            Expr::Neg(inner) => {
+                let ty = self.infer(env, inner)?;
+                self.unify(&ty, &Type::TNum, span)?;
+                Ok(Type::TNum)
+            }
+            Expr::Add(lhs, rhs) | Expr::Sub(lhs, rhs) | Expr::Mul(lhs, rhs) | Expr::Div(lhs, rhs) => {
+                let lty = self.infer(env, lhs)?;
+                self.unify(&lty, &Type::TNum, lhs.1)?;
+                let rty = self.infer(env, rhs)?;
+                self.unify(&rty, &Type::TNum, rhs.1)?;
+                Ok(Type::TNum)
+            }
+
+            Expr::Eq(lhs, rhs) | Expr::Lt(lhs, rhs) | Expr::Gt(lhs, rhs) => {
+                let lty = self.infer(env, lhs)?;
+                self.unify(&lty, &Type::TNum, lhs.1)?;
+                let rty = self.infer(env, rhs)?;
+                self.unify(&rty, &Type::TNum, rhs.1)?;
+                Ok(Type::TBool)
+            }
+
+            Expr::List(items) => {
+                let elem = self.fresh();
+                for item in items {
+                    let ty = self.infer(env, item)?;
+                    self.unify(&ty, &elem, item.1)?;
+                }
+                Ok(Type::TList(Box::new(elem)))
+            }
+
+            Expr::If { cond, then, r#else } => {
+                let condty = self.infer(env, cond)?;
+                self.unify(&condty, &Type::TBool, cond.1)?;
+                let thenty = self.infer(env, then)?;
+                let elsety = self.infer(env, r#else)?;
+                self.unify(&elsety, &thenty, r#else.1)?;
+                Ok(thenty)
+            }
+
+            Expr::Binding { value, .. } => self.infer(env, value),
+
+            Expr::LetIn { bindings, body } => {
+                let mut env = env.clone();
+                for binding in bindings {
+                    let Expr::Binding { name, value } = &binding.0 else {
+                        return Err(TypeError {
+                            message: "let-in binding is not a `Binding` node".to_string(),
+                            span: binding.1,
+                        });
+                    };
+                    let ty = self.infer(&env, value)?;
+                    let scheme = self.generalize(&env, &ty);
+                    env.insert((*name).to_string(), scheme);
+                }
+                self.infer(&env, body)
+            }
+
+            Expr::Call(f, args) => {
+                let scheme = env
+                    .get(*f)
+                    .ok_or_else(|| TypeError {
+                        message: format!("unbound name `{f}`"),
+                        span,
+                    })?
+                    .clone();
+                let mut fty = self.instantiate(&scheme);
+                for arg in args {
+                    let argty = self.infer(env, arg)?;
+                    let result = self.fresh();
+                    self.unify(
+                        &fty,
+                        &Type::TArrow(Box::new(argty), Box::new(result.clone())),
+                        arg.1,
+                    )?;
+                    fty = result;
+                }
+                Ok(fty)
+            }
+
+            Expr::Lambda { arg, body } => {
+                let argvar = self.fresh();
+                let mut env = env.clone();
+                env.insert(
+                    (*arg).to_string(),
+                    Scheme {
+                        vars: Vec::new(),
+                        ty: argvar.clone(),
+                    },
+                );
+                let bodyty = self.infer(&env, body)?;
+                Ok(Type::TArrow(Box::new(argvar), Box::new(bodyty)))
+            }
+        }
+    }
+
+    /// Replace a scheme's quantified variables with fresh type variables.
+    fn instantiate(&mut self, scheme: &Scheme) -> Type {
+        let fresh_vars: HashMap<usize, Type> =
+            scheme.vars.iter().map(|&v| (v, self.fresh())).collect();
+        substitute(&scheme.ty, &fresh_vars)
+    }
+
+    /// Quantify over the type variables in `ty` that are free in `ty` but not
+    /// free anywhere in `env`, producing a reusable scheme for `let`-bound
+    /// names.
+    fn generalize(&self, env: &Env, ty: &Type) -> Scheme {
+        let ty = self.resolve(ty);
+        let env_vars = env
+            .values()
+            .flat_map(|scheme| free_vars(&self.resolve(&scheme.ty)))
+            .collect::<Vec<_>>();
+        let vars = free_vars(&ty)
+            .into_iter()
+            .filter(|v| !env_vars.contains(v))
+            .collect();
+        Scheme { vars, ty }
+    }
+
+    /// Walk two types through the current substitution and unify them,
+    /// binding type variables as needed. On a mismatch, `a` is reported as
+    /// the type found and `b` as the type expected.
+    fn unify(&mut self, a: &Type, b: &Type, span: Span) -> Result<(), TypeError> {
+        let a = self.resolve(a);
+        let b = self.resolve(b);
+        match (&a, &b) {
+            (Type::TVar(x), Type::TVar(y)) if x == y => Ok(()),
+            (Type::TVar(v), other) | (other, Type::TVar(v)) => self.bind(*v, other, span),
+            (Type::TNum, Type::TNum) | (Type::TBool, Type::TBool) | (Type::TStr, Type::TStr) => {
+                Ok(())
+            }
+            (Type::TArrow(a1, r1), Type::TArrow(a2, r2)) => {
+                self.unify(a1, a2, span)?;
+                self.unify(r1, r2, span)
+            }
+            (Type::TList(e1), Type::TList(e2)) => self.unify(e1, e2, span),
+            _ => Err(TypeError {
+                message: format!("type mismatch: expected {b}, found {a}"),
+                span,
+            }),
+        }
+    }
+
+    fn bind(&mut self, v: usize, ty: &Type, span: Span) -> Result<(), TypeError> {
+        if let Type::TVar(other) = ty
+            && *other == v
+        {
+            return Ok(());
+        }
+        if occurs(v, ty) {
+            return Err(TypeError {
+                message: format!("infinite type: `t{v}` occurs in {ty}"),
+                span,
+            });
+        }
+        self.subst.insert(v, ty.clone());
+        Ok(())
+    }
+
+    /// Fully resolve `ty` through the current substitution.
+    fn resolve(&self, ty: &Type) -> Type {
+        match ty {
+            Type::TVar(v) => match self.subst.get(v) {
+                Some(resolved) => self.resolve(resolved),
+                None => ty.clone(),
+            },
+            Type::TArrow(arg, ret) => Type::TArrow(
+                Box::new(self.resolve(arg)),
+                Box::new(self.resolve(ret)),
+            ),
+            Type::TList(elem) => Type::TList(Box::new(self.resolve(elem))),
+            _ => ty.clone(),
+        }
+    }
+}
+
+fn substitute(ty: &Type, map: &HashMap<usize, Type>) -> Type {
+    match ty {
+        Type::TVar(v) => map.get(v).cloned().unwrap_or_else(|| ty.clone()),
+        Type::TArrow(arg, ret) => Type::TArrow(
+            Box::new(substitute(arg, map)),
+            Box::new(substitute(ret, map)),
+        ),
+        Type::TList(elem) => Type::TList(Box::new(substitute(elem, map))),
+        _ => ty.clone(),
+    }
+}
+
+fn free_vars(ty: &Type) -> Vec<usize> {
+    match ty {
+        Type::TVar(v) => vec![*v],
+        Type::TArrow(arg, ret) => {
+            let mut vars = free_vars(arg);
+            vars.extend(free_vars(ret));
+            vars
+        }
+        Type::TList(elem) => free_vars(elem),
+        _ => Vec::new(),
+    }
+}
+
+fn occurs(v: usize, ty: &Type) -> bool {
+    match ty {
+        Type::TVar(other) => *other == v,
+        Type::TArrow(arg, ret) => occurs(v, arg) || occurs(v, ret),
+        Type::TList(elem) => occurs(v, elem),
+        _ => false,
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::TVar(v) => write!(f, "t{v}"),
+            Type::TNum => write!(f, "number"),
+            Type::TBool => write!(f, "bool"),
+            Type::TStr => write!(f, "string"),
+            Type::TList(elem) => write!(f, "[{elem}]"),
+            Type::TArrow(arg, ret) => write!(f, "({arg} -> {ret})"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chumsky::{input::Input, Parser};
+
+    use crate::{lexer, parser};
+
+    fn infer_source(src: &str) -> Result<Type, TypeError> {
+        let tokens = lexer::lexer().parse(src).into_result().unwrap();
+        let eoi = Span::splat(src.len());
+        let ast = parser()
+            .parse(tokens.as_slice().map(eoi, |(t, s)| (t, s)))
+            .into_result()
+            .unwrap();
+        Infer::new().infer_expr(&ast)
+    }
+
+    #[test]
+    fn infers_arithmetic() {
+        assert_eq!(infer_source("1 + 2 * 3").unwrap(), Type::TNum);
+    }
+
+    #[test]
+    fn infers_lambda_arrow() {
+        let ty = infer_source("x: x + 1").unwrap();
+        assert_eq!(ty, Type::TArrow(Box::new(Type::TNum), Box::new(Type::TNum)));
+    }
+
+    #[test]
+    fn generalizes_let_bound_identity() {
+        // `id` is used at both `number` and `bool`, which only type-checks if
+        // `let`-bound schemes are generalized rather than fixed to their
+        // first use.
+        let ty = infer_source("let id = x: x; in if id true then id 1 else id 2").unwrap();
+        assert_eq!(ty, Type::TNum);
+    }
+
+    #[test]
+    fn rejects_mismatched_if_branches() {
+        let err = infer_source("if true then 1 else true").unwrap_err();
+        assert!(err.message.contains("type mismatch"));
+    }
+
+    #[test]
+    fn occurs_check_rejects_infinite_type() {
+        let mut infer = Infer::new();
+        let v = Type::TVar(0);
+        let span = Span::splat(0);
+        let self_referential = Type::TArrow(Box::new(v.clone()), Box::new(v.clone()));
+        let err = infer.unify(&v, &self_referential, span).unwrap_err();
+        assert!(err.message.contains("infinite type"));
+    }
+}